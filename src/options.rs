@@ -5,7 +5,7 @@
 /// `display_size` being set is the minimum requirement.
 ///
 #[derive(Clone)]
-pub struct ModelOptions {
+pub struct ModelOptions<O: OffsetProvider = NoOffset> {
     /// Specify display color ordering
     pub(crate) color_order: ColorOrder,
     /// Initial display orientation (without inverts)
@@ -14,43 +14,56 @@ pub struct ModelOptions {
     pub(crate) invert_vertical_refresh: bool,
     /// Set to make display horizontal refresh right to left
     pub(crate) invert_horizontal_refresh: bool,
-    /// Offset override function returning (w, h) offset for current
-    /// display orientation if display is "clipped" and needs an offset for (e.g. Pico v1)
-    pub(crate) window_offset_handler: fn(&ModelOptions) -> WindowOffsetResult,
-    /// Display size (w, h) override for the display/model, (0, 0) for no override
+    /// Offset provider returning the (w, h) offset for current display orientation
+    /// if display is "clipped" and needs an offset for (e.g. Pico v1). Owned rather
+    /// than `&'static`, so it can carry state computed at runtime, e.g. per-unit
+    /// calibration data
+    pub(crate) window_offset_handler: O,
+    /// Display size (w, h) for the display/model
     pub(crate) display_size: (u16, u16),
-    /// Framebuffer size (w, h) override for the display/model, (0, 0) for no override
-    pub(crate) framebuffer_size: (u16, u16),
+    /// Framebuffer size (w, h) override for the display/model, `None` for no override
+    pub(crate) framebuffer_size: Option<(u16, u16)>,
+    /// MADCTL bit mapping, to support panels whose mirror/order bits don't follow
+    /// the ILI9341 reference assignment
+    pub(crate) madctl_map: &'static dyn MadctlMap,
     // cached offset values in case we can re-use them
     cached_offset: Option<WindowOffsetResult>,
 }
 
-impl ModelOptions {
+impl ModelOptions<NoOffset> {
     ///
-    /// Constructs a [ModelOptions]
-    /// with given display and framebuffer sizes
+    /// Constructs a [ModelOptions] with given display and framebuffer sizes,
+    /// `(0, 0)` for no framebuffer size override
     ///
     pub fn with_sizes(display_size: (u16, u16), framebuffer_size: (u16, u16)) -> Self {
-        Self {
-            color_order: ColorOrder::default(),
-            orientation: Orientation::default(),
-            invert_horizontal_refresh: false,
-            invert_vertical_refresh: false,
-            window_offset_handler: no_offset,
-            display_size,
-            framebuffer_size,
-            cached_offset: None,
-        }
+        Self::with_all(display_size, framebuffer_size, NoOffset)
+    }
+
+    ///
+    /// Constructs a [ModelOptions] with a type-level [DisplaySize] and no
+    /// framebuffer size override
+    ///
+    pub fn with_display_size<DS: DisplaySize>() -> Self {
+        Self::with_all(DS::size(), (0, 0), NoOffset)
     }
 
     ///
-    /// Constructs a [ModelOptions]
-    /// with given display and framebuffer sizes and provided window offset handler
+    /// Starts a [ModelOptionsBuilder] for the given type-level [DisplaySize]
+    ///
+    pub fn builder<DS: DisplaySize>() -> ModelOptionsBuilder<NoOffset> {
+        ModelOptionsBuilder::new::<DS>()
+    }
+}
+
+impl<O: OffsetProvider> ModelOptions<O> {
+    ///
+    /// Constructs a [ModelOptions] with given display and framebuffer sizes and
+    /// provided window offset handler, `(0, 0)` for no framebuffer size override
     ///
     pub fn with_all(
         display_size: (u16, u16),
         framebuffer_size: (u16, u16),
-        window_offset_handler: fn(&ModelOptions) -> WindowOffsetResult,
+        window_offset_handler: O,
     ) -> Self {
         Self {
             color_order: ColorOrder::default(),
@@ -59,28 +72,45 @@ impl ModelOptions {
             invert_vertical_refresh: false,
             window_offset_handler,
             display_size,
-            framebuffer_size,
+            framebuffer_size: if framebuffer_size == (0, 0) {
+                None
+            } else {
+                Some(framebuffer_size)
+            },
+            madctl_map: &DEFAULT_MADCTL_MAP,
             cached_offset: None,
         }
     }
 
+    ///
+    /// Constructs a [ModelOptions] with type-level [DisplaySize]s for the display
+    /// and framebuffer, and a provided window offset handler
+    ///
+    pub fn with_display_size_all<DS: DisplaySize, FB: DisplaySize>(
+        window_offset_handler: O,
+    ) -> Self {
+        Self::with_all(DS::size(), FB::size(), window_offset_handler)
+    }
+
+    ///
+    /// Overrides the [MadctlMap] used to compute the MADCTL register value and whether
+    /// the current orientation swaps width and height. Defaults to [DefaultMadctlMap].
+    ///
+    pub fn with_madctl_map(mut self, madctl_map: &'static dyn MadctlMap) -> Self {
+        self.madctl_map = madctl_map;
+        self
+    }
+
     ///
     /// Returns MADCTL register value for given display options
     ///
     pub fn madctl(&self) -> u8 {
-        let mut value = self.orientation.value_u8();
-        if self.invert_vertical_refresh {
-            value |= 0b0001_0000;
-        }
-        match self.color_order {
-            ColorOrder::Rgb => {}
-            ColorOrder::Bgr => value |= 0b0000_1000,
-        }
-        if self.invert_horizontal_refresh {
-            value |= 0b0000_0100;
-        }
-
-        value
+        self.madctl_map.madctl(
+            self.orientation,
+            self.color_order,
+            self.invert_vertical_refresh,
+            self.invert_horizontal_refresh,
+        )
     }
 
     ///
@@ -88,7 +118,7 @@ impl ModelOptions {
     /// Used by models.
     ///
     pub fn display_size(&self) -> (u16, u16) {
-        Self::orient_size(self.display_size, self.orientation())
+        self.orient_size(self.display_size, self.orientation())
     }
 
     ///
@@ -96,13 +126,9 @@ impl ModelOptions {
     /// Used by models. Uses display_size if framebuffer_size is not set.
     ///
     pub fn framebuffer_size(&self) -> (u16, u16) {
-        let size = if self.framebuffer_size == (0, 0) {
-            self.display_size
-        } else {
-            self.framebuffer_size
-        };
+        let size = self.framebuffer_size.unwrap_or(self.display_size);
 
-        Self::orient_size(size, self.orientation())
+        self.orient_size(size, self.orientation())
     }
 
     ///
@@ -113,7 +139,12 @@ impl ModelOptions {
         if let Some(cached) = self.cached_offset {
             cached.into()
         } else {
-            let result = (self.window_offset_handler)(self);
+            let framebuffer_size = self.framebuffer_size.unwrap_or(self.display_size);
+            let result = self.window_offset_handler.offset(
+                self.display_size,
+                framebuffer_size,
+                self.orientation,
+            );
 
             if result.cachable {
                 self.cached_offset = Some(result);
@@ -128,17 +159,22 @@ impl ModelOptions {
     }
 
     ///
-    /// Sets the current [Orientation]
+    /// Sets the current [Orientation]. Invalidates the cached window offset, since an
+    /// [OffsetProvider] (e.g. [OrientationTableOffset]) may return a different result
+    /// for the new orientation.
     ///
     pub fn set_orientation(&mut self, orientation: Orientation) {
         self.orientation = orientation;
+        self.cached_offset = None;
     }
 
-    // Flip size according to orientation, in general
-    fn orient_size(size: (u16, u16), orientation: Orientation) -> (u16, u16) {
-        match orientation {
-            Orientation::Portrait(_) | Orientation::PortraitInverted(_) => size,
-            Orientation::Landscape(_) | Orientation::LandscapeInverted(_) => (size.1, size.0),
+    // Flip size according to orientation, consulting the current MadctlMap to know
+    // whether the orientation swaps width and height
+    fn orient_size(&self, size: (u16, u16), orientation: Orientation) -> (u16, u16) {
+        if self.madctl_map.swaps_dimensions(orientation) {
+            (size.1, size.0)
+        } else {
+            size
         }
     }
 }
@@ -167,33 +203,277 @@ impl From<WindowOffsetResult> for (u16, u16) {
 }
 
 ///
-/// `no_offset` is the default offset provider. It results to 0, 0 in case display_size is == framebuffer_size
+/// Supplies the (w, h) clip offset for the current display options. Unlike a bare
+/// `fn(&ModelOptions) -> WindowOffsetResult`, an [OffsetProvider] is owned by
+/// [ModelOptions] rather than borrowed `'static`, so it can be a struct holding its
+/// own state, e.g. per-unit calibration data computed at runtime, or an orientation ->
+/// offset lookup table.
+///
+pub trait OffsetProvider {
+    /// Returns the (w, h) offset to use for the given display size, framebuffer size
+    /// and orientation. Set [WindowOffsetResult::cachable] if the result is stable
+    /// across calls with the same orientation so [ModelOptions::window_offset] can
+    /// cache it.
+    fn offset(
+        &self,
+        display_size: (u16, u16),
+        framebuffer_size: (u16, u16),
+        orientation: Orientation,
+    ) -> WindowOffsetResult;
+}
+
+///
+/// The default [OffsetProvider]. It results to 0, 0 in case display_size is == framebuffer_size
 /// and to framebuffer_size - display_size otherwise.
 ///
-fn no_offset(options: &ModelOptions) -> WindowOffsetResult {
-    let fb_w = options.framebuffer_size.0;
-    let fb_h = options.framebuffer_size.1;
-    let d_w = options.display_size.0;
-    let d_h = options.display_size.1;
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoOffset;
 
-    let mut x = 0;
-    let mut y = 0;
+impl OffsetProvider for NoOffset {
+    fn offset(
+        &self,
+        display_size: (u16, u16),
+        framebuffer_size: (u16, u16),
+        _orientation: Orientation,
+    ) -> WindowOffsetResult {
+        let (fb_w, fb_h) = framebuffer_size;
+        let (d_w, d_h) = display_size;
 
-    if fb_w > d_w {
-        x = fb_w - d_w;
+        let mut x = 0;
+        let mut y = 0;
+
+        if fb_w > d_w {
+            x = fb_w - d_w;
+        }
+
+        if fb_h > d_h {
+            y = fb_h - d_h;
+        }
+
+        WindowOffsetResult {
+            x,
+            y,
+            cachable: true,
+        }
     }
+}
+
+///
+/// A clip offset per [Orientation], for displays that are physically offset only in
+/// certain rotations (e.g. "Pico v1" style panels). Passed to [ModelOptionsBuilder::orientation_offsets]
+///
+#[derive(Debug, Default, Clone, Copy)]
+pub struct OrientationOffsets {
+    /// Offset used in [Orientation::Portrait]
+    pub portrait: (u16, u16),
+    /// Offset used in [Orientation::PortraitInverted]
+    pub portrait_inverted: (u16, u16),
+    /// Offset used in [Orientation::Landscape]
+    pub landscape: (u16, u16),
+    /// Offset used in [Orientation::LandscapeInverted]
+    pub landscape_inverted: (u16, u16),
+}
 
-    if fb_h > d_w {
-        y = fb_h - d_h;
+///
+/// [OffsetProvider] that looks up the clip offset from its [OrientationOffsets] table,
+/// keyed by the current orientation. Built with [ModelOptionsBuilder::orientation_offsets]
+///
+#[derive(Debug, Default, Clone, Copy)]
+pub struct OrientationTableOffset {
+    offsets: OrientationOffsets,
+}
+
+impl OffsetProvider for OrientationTableOffset {
+    fn offset(
+        &self,
+        _display_size: (u16, u16),
+        _framebuffer_size: (u16, u16),
+        orientation: Orientation,
+    ) -> WindowOffsetResult {
+        let (x, y) = match orientation {
+            Orientation::Portrait(_) => self.offsets.portrait,
+            Orientation::PortraitInverted(_) => self.offsets.portrait_inverted,
+            Orientation::Landscape(_) => self.offsets.landscape,
+            Orientation::LandscapeInverted(_) => self.offsets.landscape_inverted,
+        };
+
+        WindowOffsetResult {
+            x,
+            y,
+            cachable: true,
+        }
     }
+}
+
+///
+/// Fluent builder for [ModelOptions], covering [ColorOrder], refresh inversion, initial
+/// [Orientation] and a per-orientation offset table for panels that are physically
+/// clipped only in certain rotations, so callers don't need to hand-write offset
+/// providers for the common case
+///
+pub struct ModelOptionsBuilder<O: OffsetProvider = NoOffset> {
+    display_size: (u16, u16),
+    framebuffer_size: Option<(u16, u16)>,
+    color_order: ColorOrder,
+    orientation: Orientation,
+    invert_vertical_refresh: bool,
+    invert_horizontal_refresh: bool,
+    madctl_map: &'static dyn MadctlMap,
+    window_offset_handler: O,
+}
 
-    WindowOffsetResult {
-        x,
-        y,
-        cachable: true,
+impl ModelOptionsBuilder<NoOffset> {
+    ///
+    /// Starts a new builder for the given type-level [DisplaySize] and no framebuffer
+    /// size override
+    ///
+    pub fn new<DS: DisplaySize>() -> Self {
+        Self {
+            display_size: DS::size(),
+            framebuffer_size: None,
+            color_order: ColorOrder::default(),
+            orientation: Orientation::default(),
+            invert_vertical_refresh: false,
+            invert_horizontal_refresh: false,
+            madctl_map: &DEFAULT_MADCTL_MAP,
+            window_offset_handler: NoOffset,
+        }
     }
 }
 
+impl<O: OffsetProvider> ModelOptionsBuilder<O> {
+    ///
+    /// Overrides the framebuffer size with the given type-level [DisplaySize]
+    ///
+    pub fn framebuffer_size<FB: DisplaySize>(mut self) -> Self {
+        self.framebuffer_size = Some(FB::size());
+        self
+    }
+
+    ///
+    /// Sets the [ColorOrder]
+    ///
+    pub fn color_order(mut self, color_order: ColorOrder) -> Self {
+        self.color_order = color_order;
+        self
+    }
+
+    ///
+    /// Sets the initial [Orientation]
+    ///
+    pub fn orientation(mut self, orientation: Orientation) -> Self {
+        self.orientation = orientation;
+        self
+    }
+
+    ///
+    /// Sets whether the display vertical refresh runs bottom to top
+    ///
+    pub fn invert_vertical_refresh(mut self, invert: bool) -> Self {
+        self.invert_vertical_refresh = invert;
+        self
+    }
+
+    ///
+    /// Sets whether the display horizontal refresh runs right to left
+    ///
+    pub fn invert_horizontal_refresh(mut self, invert: bool) -> Self {
+        self.invert_horizontal_refresh = invert;
+        self
+    }
+
+    ///
+    /// Overrides the [MadctlMap] used to compute the MADCTL register value
+    ///
+    pub fn madctl_map(mut self, madctl_map: &'static dyn MadctlMap) -> Self {
+        self.madctl_map = madctl_map;
+        self
+    }
+
+    ///
+    /// Overrides the [OffsetProvider], changing the builder's provider type accordingly
+    ///
+    pub fn window_offset_handler<O2: OffsetProvider>(
+        self,
+        window_offset_handler: O2,
+    ) -> ModelOptionsBuilder<O2> {
+        ModelOptionsBuilder {
+            display_size: self.display_size,
+            framebuffer_size: self.framebuffer_size,
+            color_order: self.color_order,
+            orientation: self.orientation,
+            invert_vertical_refresh: self.invert_vertical_refresh,
+            invert_horizontal_refresh: self.invert_horizontal_refresh,
+            madctl_map: self.madctl_map,
+            window_offset_handler,
+        }
+    }
+
+    ///
+    /// Sets a per-orientation clip offset table. The resulting [ModelOptions] will use a
+    /// cached, orientation-keyed [OrientationTableOffset] instead of requiring a
+    /// hand-written [OffsetProvider]
+    ///
+    pub fn orientation_offsets(
+        self,
+        offsets: OrientationOffsets,
+    ) -> ModelOptionsBuilder<OrientationTableOffset> {
+        self.window_offset_handler(OrientationTableOffset { offsets })
+    }
+
+    ///
+    /// Builds the [ModelOptions]
+    ///
+    pub fn build(self) -> ModelOptions<O> {
+        ModelOptions {
+            color_order: self.color_order,
+            orientation: self.orientation,
+            invert_vertical_refresh: self.invert_vertical_refresh,
+            invert_horizontal_refresh: self.invert_horizontal_refresh,
+            window_offset_handler: self.window_offset_handler,
+            display_size: self.display_size,
+            framebuffer_size: self.framebuffer_size,
+            madctl_map: self.madctl_map,
+            cached_offset: None,
+        }
+    }
+}
+
+///
+/// Marker trait encoding a display's dimensions at the type level, so a model's
+/// geometry is checked by the compiler instead of relying on a runtime `(u16, u16)`.
+///
+pub trait DisplaySize {
+    /// Width in pixels
+    const WIDTH: u16;
+    /// Height in pixels
+    const HEIGHT: u16;
+
+    /// Returns the `(width, height)` tuple for this size, as used internally by [ModelOptions]
+    fn size() -> (u16, u16) {
+        (Self::WIDTH, Self::HEIGHT)
+    }
+}
+
+macro_rules! display_size {
+    ($name:ident, $width:expr, $height:expr) => {
+        #[doc = concat!("Display size for displays with a resolution of ", stringify!($width), "x", stringify!($height))]
+        #[derive(Debug, Default, Clone, Copy)]
+        pub struct $name;
+
+        impl DisplaySize for $name {
+            const WIDTH: u16 = $width;
+            const HEIGHT: u16 = $height;
+        }
+    };
+}
+
+display_size!(DisplaySize128x128, 128, 128);
+display_size!(DisplaySize128x160, 128, 160);
+display_size!(DisplaySize135x240, 135, 240);
+display_size!(DisplaySize240x320, 240, 320);
+display_size!(DisplaySize320x480, 320, 480);
+
 ///
 /// Display orientation.
 ///
@@ -216,20 +496,123 @@ impl Default for Orientation {
 }
 
 impl Orientation {
+    /// Returns the MADCTL MY/MX/MV bits for this orientation under the
+    /// [DefaultMadctlMap] (ILI9341 reference assignment), with no color order or
+    /// refresh inverts applied. Kept for callers that only want the orientation bits;
+    /// delegates to [DefaultMadctlMap::madctl] so there's a single MADCTL bit table.
     pub fn value_u8(&self) -> u8 {
-        match self {
-            Orientation::Portrait(false) => 0b0000_0000,
-            Orientation::Portrait(true) => 0b0100_0000,
-            Orientation::PortraitInverted(false) => 0b1100_0000,
-            Orientation::PortraitInverted(true) => 0b1000_0000,
-            Orientation::Landscape(false) => 0b0010_0000,
-            Orientation::Landscape(true) => 0b0110_0000,
-            Orientation::LandscapeInverted(false) => 0b1110_0000,
-            Orientation::LandscapeInverted(true) => 0b1010_0000,
+        DEFAULT_MADCTL_MAP.madctl(*self, ColorOrder::Rgb, false, false)
+    }
+}
+
+///
+/// Maps [Orientation] and [ColorOrder] to the MADCTL register bits a panel expects.
+/// The [DefaultMadctlMap] follows the ILI9341 reference assignment
+/// (MY=0x80, MX=0x40, MV=0x20, ML=0x10, BGR=0x08, MH=0x04). Boards whose row/column
+/// mirror bits are swapped relative to that reference only need to override the
+/// individual `*_bit` methods below instead of reimplementing [Self::madctl] and
+/// re-deriving [Orientation]'s MY/MX/MV table from scratch. The `*_bit` methods only
+/// choose which bit is set for each flag; a panel whose mirror bits have inverted
+/// polarity overrides [Self::madctl] itself.
+///
+pub trait MadctlMap {
+    /// Bit set to mirror the row address order (MY)
+    fn my_bit(&self) -> u8 {
+        0b1000_0000
+    }
+
+    /// Bit set to mirror the column address order (MX)
+    fn mx_bit(&self) -> u8 {
+        0b0100_0000
+    }
+
+    /// Bit set to swap row/column, i.e. landscape orientations (MV)
+    fn mv_bit(&self) -> u8 {
+        0b0010_0000
+    }
+
+    /// Bit set when `invert_vertical_refresh` is requested (ML)
+    fn ml_bit(&self) -> u8 {
+        0b0001_0000
+    }
+
+    /// Bit set for [ColorOrder::Bgr] (BGR)
+    fn bgr_bit(&self) -> u8 {
+        0b0000_1000
+    }
+
+    /// Bit set when `invert_horizontal_refresh` is requested (MH)
+    fn mh_bit(&self) -> u8 {
+        0b0000_0100
+    }
+
+    /// Returns the MADCTL register value for the given display options
+    fn madctl(
+        &self,
+        orientation: Orientation,
+        color_order: ColorOrder,
+        invert_vertical_refresh: bool,
+        invert_horizontal_refresh: bool,
+    ) -> u8 {
+        let (my, mx, mv) = orientation_mirror_bits(orientation);
+
+        let mut value = 0;
+        if my {
+            value |= self.my_bit();
+        }
+        if mx {
+            value |= self.mx_bit();
         }
+        if mv {
+            value |= self.mv_bit();
+        }
+        if invert_vertical_refresh {
+            value |= self.ml_bit();
+        }
+        if let ColorOrder::Bgr = color_order {
+            value |= self.bgr_bit();
+        }
+        if invert_horizontal_refresh {
+            value |= self.mh_bit();
+        }
+
+        value
+    }
+
+    /// Returns `true` if `orientation` swaps the display's width and height
+    fn swaps_dimensions(&self, orientation: Orientation) -> bool {
+        matches!(
+            orientation,
+            Orientation::Landscape(_) | Orientation::LandscapeInverted(_)
+        )
+    }
+}
+
+// Decomposes an Orientation into its (MY, MX, MV) mirror/swap flags, independent of
+// which physical bit a MadctlMap assigns to each
+fn orientation_mirror_bits(orientation: Orientation) -> (bool, bool, bool) {
+    match orientation {
+        Orientation::Portrait(false) => (false, false, false),
+        Orientation::Portrait(true) => (false, true, false),
+        Orientation::PortraitInverted(false) => (true, true, false),
+        Orientation::PortraitInverted(true) => (true, false, false),
+        Orientation::Landscape(false) => (false, false, true),
+        Orientation::Landscape(true) => (false, true, true),
+        Orientation::LandscapeInverted(false) => (true, true, true),
+        Orientation::LandscapeInverted(true) => (true, false, true),
     }
 }
 
+///
+/// The default [MadctlMap], following the ILI9341 reference MADCTL bit assignment
+///
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DefaultMadctlMap;
+
+static DEFAULT_MADCTL_MAP: DefaultMadctlMap = DefaultMadctlMap;
+
+impl MadctlMap for DefaultMadctlMap {}
+
 ///
 /// Tearing effect output setting.
 ///
@@ -257,3 +640,46 @@ impl Default for ColorOrder {
         Self::Rgb
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_madctl_map_matches_orientation_value_u8() {
+        let orientations = [
+            Orientation::Portrait(false),
+            Orientation::Portrait(true),
+            Orientation::PortraitInverted(false),
+            Orientation::PortraitInverted(true),
+            Orientation::Landscape(false),
+            Orientation::Landscape(true),
+            Orientation::LandscapeInverted(false),
+            Orientation::LandscapeInverted(true),
+        ];
+
+        for orientation in orientations {
+            assert_eq!(
+                DefaultMadctlMap.madctl(orientation, ColorOrder::Rgb, false, false),
+                orientation.value_u8(),
+            );
+        }
+    }
+
+    #[test]
+    fn set_orientation_invalidates_cached_window_offset() {
+        let mut options = ModelOptionsBuilder::new::<DisplaySize128x128>()
+            .orientation_offsets(OrientationOffsets {
+                portrait: (1, 2),
+                portrait_inverted: (3, 4),
+                landscape: (5, 6),
+                landscape_inverted: (7, 8),
+            })
+            .build();
+
+        assert_eq!(options.window_offset(), (1, 2));
+
+        options.set_orientation(Orientation::Landscape(false));
+        assert_eq!(options.window_offset(), (5, 6));
+    }
+}